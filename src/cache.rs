@@ -0,0 +1,354 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hasher;
+use serde::{Serialize, Deserialize};
+use crate::{DrawCmd, DrawData, DrawList, FxHasher};
+
+const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+/// Returned by [`DrawData::from_delta`] when a [`CmdListRef::Cached`] entry references
+/// a hash this cache doesn't hold — the sender and receiver caches have desynced, most
+/// likely because a `Full` frame was lost in transit. The receiver should
+/// [`reset`](DrawDataCache::reset) its cache and ask the sender to reset its own as
+/// well: hashes the sender has already acknowledged stay eligible for `Cached` until
+/// its cache is reset (or evicts them), after which the next [`DrawDataDelta::encode`]
+/// sends every list `Full` again.
+#[derive(Debug)]
+pub struct CacheDesyncError(pub u64);
+
+impl fmt::Display for CacheDesyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "draw list cache desynced: no entry for hash {:#x}", self.0)
+    }
+}
+
+impl std::error::Error for CacheDesyncError {}
+
+/// Content-addressed cache of `DrawList`s, keyed by a stable hash of their contents.
+///
+/// Held on both the sender and receiver side of a remote-rendering pipeline so that
+/// [`DrawDataDelta::encode`] can skip re-transmitting `DrawList`s that are
+/// byte-identical to one already seen. Bounded to `capacity` entries, evicting the
+/// least recently *accessed* list first (true LRU: both [`insert`](Self::insert) and
+/// [`get`](Self::get) promote their entry, so a list referenced every frame is never
+/// evicted purely because other lists keep churning through the cache).
+///
+/// On the sender side, a hash is only safe to send as [`CmdListRef::Cached`] once the
+/// receiver has actually confirmed holding it — see
+/// [`acknowledge`](Self::acknowledge). Without that, a single dropped `Full` frame
+/// would permanently desync the two caches.
+#[derive(Debug)]
+pub struct DrawDataCache {
+    lists: HashMap<u64, DrawList>,
+    order: VecDeque<u64>,
+    acknowledged: HashSet<u64>,
+    capacity: usize,
+}
+
+impl Default for DrawDataCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DrawDataCache {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CACHE_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            lists: HashMap::new(),
+            order: VecDeque::new(),
+            acknowledged: HashSet::new(),
+            capacity,
+        }
+    }
+
+    /// Computes a stable content hash for `list`, suitable for use as a `CmdListRef`
+    /// cache key. Two `DrawList`s with identical indices, vertices and commands hash
+    /// the same regardless of process.
+    pub fn hash_list(list: &DrawList) -> u64 {
+        let mut hasher = FxHasher::default();
+        for idx in list.idx_buffer() {
+            hasher.write_u16(*idx);
+        }
+        for vert in list.vtx_buffer() {
+            hasher.write_u32(vert.pos[0].to_bits());
+            hasher.write_u32(vert.pos[1].to_bits());
+            hasher.write_u32(vert.uv[0].to_bits());
+            hasher.write_u32(vert.uv[1].to_bits());
+            hasher.write_u32(u32::from_ne_bytes(vert.col));
+        }
+        for cmd in &list.commands {
+            match cmd {
+                DrawCmd::Elements { count, cmd_params } => {
+                    hasher.write_u8(0);
+                    hasher.write_usize(*count);
+                    for component in cmd_params.clip_rect {
+                        hasher.write_u32(component.to_bits());
+                    }
+                    hasher.write_usize(cmd_params.texture_id.id());
+                    hasher.write_usize(cmd_params.vtx_offset);
+                    hasher.write_usize(cmd_params.idx_offset);
+                }
+                DrawCmd::ResetRenderState => hasher.write_u8(1),
+                DrawCmd::Callback { id, user_data } => {
+                    hasher.write_u8(2);
+                    hasher.write_u32(*id);
+                    hasher.write(user_data);
+                }
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Returns `true` if a list with this content hash is currently cached.
+    pub fn contains(&self, hash: u64) -> bool {
+        self.lists.contains_key(&hash)
+    }
+
+    /// Looks up `hash`, promoting it to most-recently-used if found.
+    pub fn get(&mut self, hash: u64) -> Option<&DrawList> {
+        if self.lists.contains_key(&hash) {
+            self.touch(hash);
+        }
+        self.lists.get(&hash)
+    }
+
+    /// Inserts or overwrites `hash`, promoting it to most-recently-used.
+    pub fn insert(&mut self, hash: u64, list: DrawList) {
+        let is_new = self.lists.insert(hash, list).is_none();
+        self.touch(hash);
+        if is_new {
+            self.evict();
+        }
+    }
+
+    /// Marks `hash` as confirmed present in the receiver's cache, so future frames may
+    /// reference it with [`CmdListRef::Cached`] instead of re-sending it in full. Call
+    /// this on the *sender's* cache once the receiver has confirmed (e.g. over an
+    /// explicit ack channel) that it has decoded a frame containing `hash` —
+    /// see [`DrawDataDelta::hashes`].
+    pub fn acknowledge(&mut self, hash: u64) {
+        if self.lists.contains_key(&hash) {
+            self.acknowledged.insert(hash);
+        }
+    }
+
+    /// Marks every hash in `hashes` as acknowledged, see [`acknowledge`](Self::acknowledge).
+    pub fn acknowledge_all(&mut self, hashes: impl IntoIterator<Item = u64>) {
+        for hash in hashes {
+            self.acknowledge(hash);
+        }
+    }
+
+    /// Returns `true` if `hash` is cached and has been confirmed acknowledged by the
+    /// receiver, i.e. safe for [`DrawDataDelta::encode`] to reference as `Cached`.
+    fn is_acknowledged(&self, hash: u64) -> bool {
+        self.acknowledged.contains(&hash)
+    }
+
+    /// Drops all cached entries and acknowledgment state. Call this after a
+    /// [`CacheDesyncError`] so every subsequently encoded `DrawList` is sent in full
+    /// again until new acknowledgments rebuild the cache.
+    pub fn reset(&mut self) {
+        self.lists.clear();
+        self.order.clear();
+        self.acknowledged.clear();
+    }
+
+    fn touch(&mut self, hash: u64) {
+        if let Some(pos) = self.order.iter().position(|&h| h == hash) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(hash);
+    }
+
+    fn evict(&mut self) {
+        while self.lists.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.lists.remove(&oldest);
+                self.acknowledged.remove(&oldest);
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+/// A reference to a single `DrawList` within a [`DrawDataDelta`]: either a content
+/// address the receiver is expected to already hold a copy of, or the full list
+/// alongside its address so the receiver can cache it for next time.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum CmdListRef {
+    Cached(u64),
+    Full { hash: u64, list: DrawList },
+}
+
+/// `DrawData` with `DrawList`s that are unchanged from a previous frame replaced by a
+/// reference into a [`DrawDataCache`] shared by the sender and receiver, to avoid
+/// re-transmitting lists that didn't change.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DrawDataDelta {
+    pub total_idx_count: i32,
+    pub total_vtx_count: i32,
+    pub cmd_lists: Vec<CmdListRef>,
+    pub display_pos: [f32; 2],
+    pub display_size: [f32; 2],
+    pub framebuffer_scale: [f32; 2],
+}
+
+impl DrawDataDelta {
+    /// Encodes `data` against `cache`, which represents the set of `DrawList`s the
+    /// receiver has *acknowledged* holding — see [`DrawDataCache::acknowledge`]. Lists
+    /// whose hash is already acknowledged are emitted as `Cached`; everything else
+    /// (new, changed, or not yet confirmed received) is emitted as `Full` and
+    /// inserted into `cache` so later frames can reference it once acknowledged.
+    pub fn encode(data: &DrawData, cache: &mut DrawDataCache) -> Self {
+        let cmd_lists = data
+            .cmd_lists
+            .iter()
+            .map(|list| {
+                let hash = DrawDataCache::hash_list(list);
+                if cache.is_acknowledged(hash) {
+                    cache.touch(hash);
+                    CmdListRef::Cached(hash)
+                } else {
+                    cache.insert(hash, list.clone());
+                    CmdListRef::Full { hash, list: list.clone() }
+                }
+            })
+            .collect();
+
+        Self {
+            total_idx_count: data.total_idx_count,
+            total_vtx_count: data.total_vtx_count,
+            cmd_lists,
+            display_pos: data.display_pos,
+            display_size: data.display_size,
+            framebuffer_scale: data.framebuffer_scale,
+        }
+    }
+}
+
+impl DrawDataDelta {
+    /// The hashes of every `DrawList` referenced by this delta, `Cached` or `Full`
+    /// alike. After successfully decoding this delta via [`DrawData::from_delta`], send
+    /// these back to the sender (over whatever transport carries the deltas
+    /// themselves) and pass them to [`DrawDataCache::acknowledge_all`] on the sender's
+    /// cache, so future frames may reference them as `Cached`.
+    pub fn hashes(&self) -> impl Iterator<Item = u64> + '_ {
+        self.cmd_lists.iter().map(|entry| match entry {
+            CmdListRef::Cached(hash) => *hash,
+            CmdListRef::Full { hash, .. } => *hash,
+        })
+    }
+}
+
+impl DrawData {
+    /// Reconstructs a full `DrawData` from `delta`, resolving `Cached` entries out of
+    /// `cache` and inserting any newly seen `Full` lists into it. Returns
+    /// [`CacheDesyncError`] if a `Cached` entry references a hash `cache` doesn't
+    /// hold, which means the sender and receiver caches have desynced — see
+    /// [`DrawDataCache::reset`].
+    pub fn from_delta(delta: &DrawDataDelta, cache: &mut DrawDataCache) -> Result<Self, CacheDesyncError> {
+        let cmd_lists = delta
+            .cmd_lists
+            .iter()
+            .map(|entry| match entry {
+                CmdListRef::Cached(hash) => cache.get(*hash).cloned().ok_or(CacheDesyncError(*hash)),
+                CmdListRef::Full { hash, list } => {
+                    cache.insert(*hash, list.clone());
+                    Ok(list.clone())
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            total_idx_count: delta.total_idx_count,
+            total_vtx_count: delta.total_vtx_count,
+            cmd_lists,
+            display_pos: delta.display_pos,
+            display_size: delta.display_size,
+            framebuffer_scale: delta.framebuffer_scale,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DrawIdx;
+
+    fn list_with_idx(idx: DrawIdx) -> DrawList {
+        DrawList {
+            commands: Vec::new(),
+            idx_buffer: vec![idx],
+            vtx_buffer: Vec::new(),
+        }
+    }
+
+    fn draw_data(lists: Vec<DrawList>) -> DrawData {
+        DrawData {
+            total_idx_count: 0,
+            total_vtx_count: 0,
+            cmd_lists: lists,
+            display_pos: [0.0, 0.0],
+            display_size: [0.0, 0.0],
+            framebuffer_scale: [1.0, 1.0],
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_accessed_not_least_recently_inserted() {
+        let mut cache = DrawDataCache::with_capacity(2);
+        let a = DrawDataCache::hash_list(&list_with_idx(1));
+        let b = DrawDataCache::hash_list(&list_with_idx(2));
+        let c = DrawDataCache::hash_list(&list_with_idx(3));
+
+        cache.insert(a, list_with_idx(1));
+        cache.insert(b, list_with_idx(2));
+        // Touch `a` so `b`, not `a`, is least recently used.
+        cache.get(a);
+        cache.insert(c, list_with_idx(3));
+
+        assert!(cache.contains(a), "recently accessed entry should survive eviction");
+        assert!(!cache.contains(b), "least recently used entry should be evicted");
+        assert!(cache.contains(c));
+    }
+
+    #[test]
+    fn encode_resends_full_until_acknowledged() {
+        let mut cache = DrawDataCache::new();
+        let data = draw_data(vec![list_with_idx(42)]);
+
+        let first = DrawDataDelta::encode(&data, &mut cache);
+        assert!(matches!(first.cmd_lists[0], CmdListRef::Full { .. }), "first sighting of a hash must be sent in full");
+
+        // Not yet acknowledged: encoding the same data again must still be `Full`,
+        // not `Cached`, or a lost first frame would desync the receiver forever.
+        let second = DrawDataDelta::encode(&data, &mut cache);
+        assert!(matches!(second.cmd_lists[0], CmdListRef::Full { .. }), "unacknowledged hash must not be sent as Cached");
+
+        cache.acknowledge_all(second.hashes());
+        let third = DrawDataDelta::encode(&data, &mut cache);
+        assert!(matches!(third.cmd_lists[0], CmdListRef::Cached(_)), "acknowledged hash should be sent as Cached");
+    }
+
+    #[test]
+    fn from_delta_reports_desync_for_an_unknown_cached_hash() {
+        let mut cache = DrawDataCache::new();
+        let delta = DrawDataDelta {
+            total_idx_count: 0,
+            total_vtx_count: 0,
+            cmd_lists: vec![CmdListRef::Cached(0xdead_beef)],
+            display_pos: [0.0, 0.0],
+            display_size: [0.0, 0.0],
+            framebuffer_scale: [1.0, 1.0],
+        };
+
+        let err = DrawData::from_delta(&delta, &mut cache).unwrap_err();
+        assert_eq!(err.0, 0xdead_beef);
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::fmt;
+
+type Callback = Box<dyn Fn(&[u8]) + Send + Sync>;
+
+/// Registry of draw-list callbacks, keyed by a stable id instead of a raw function
+/// pointer, so a [`DrawCmd::Callback`](crate::DrawCmd::Callback) can cross the
+/// serialization boundary.
+///
+/// The sender registers each callback closure under an id before encoding a frame,
+/// serializing only the id (and an optional opaque `user_data` payload); the receiver
+/// looks the id up in its own registry, populated the same way, and invokes the
+/// matching closure during playback via [`invoke`](CallbackRegistry::invoke).
+#[derive(Default)]
+pub struct CallbackRegistry {
+    callbacks: HashMap<u32, Callback>,
+}
+
+impl CallbackRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `callback` under `id`, replacing any callback previously registered
+    /// under the same id.
+    pub fn register(&mut self, id: u32, callback: impl Fn(&[u8]) + Send + Sync + 'static) {
+        self.callbacks.insert(id, Box::new(callback));
+    }
+
+    pub fn unregister(&mut self, id: u32) {
+        self.callbacks.remove(&id);
+    }
+
+    /// Invokes the callback registered under `id` with `user_data`.
+    ///
+    /// Returns [`UnregisteredCallbackError`] instead of panicking if no callback is
+    /// registered under `id`, so playback of an unrecognized `DrawCmd::Callback` can
+    /// be handled (skipped, logged) rather than crashing the renderer.
+    pub fn invoke(&self, id: u32, user_data: &[u8]) -> Result<(), UnregisteredCallbackError> {
+        match self.callbacks.get(&id) {
+            Some(callback) => {
+                callback(user_data);
+                Ok(())
+            }
+            None => Err(UnregisteredCallbackError { id: Some(id) }),
+        }
+    }
+}
+
+/// Returned when a callback can't be resolved: either a `DrawCmd::Callback` whose id
+/// hasn't been registered on the receiving side (`id` is `Some`), or an
+/// `imgui::DrawCmd::RawCallback` that has no stable id to serialize in the first
+/// place (`id` is `None`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UnregisteredCallbackError {
+    pub id: Option<u32>,
+}
+
+impl UnregisteredCallbackError {
+    #[cfg(feature = "imgui")]
+    pub(crate) fn unresolvable() -> Self {
+        Self { id: None }
+    }
+}
+
+impl fmt::Display for UnregisteredCallbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.id {
+            Some(id) => write!(f, "no callback registered for id {}", id),
+            None => write!(f, "callback has no stable id to serialize"),
+        }
+    }
+}
+
+impl std::error::Error for UnregisteredCallbackError {}
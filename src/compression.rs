@@ -0,0 +1,147 @@
+use std::cell::Cell;
+use std::fmt;
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
+
+/// Returned by [`Codec::decompress`] (and thus [`Compressed::decode`]) when a
+/// compressed payload is corrupt or truncated, instead of panicking — the payload may
+/// have come from an untrusted or unreliable remote source.
+#[derive(Debug)]
+pub struct CompressionError(String);
+
+impl fmt::Display for CompressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "compression codec error: {}", self.0)
+    }
+}
+
+impl std::error::Error for CompressionError {}
+
+/// Upper bound on the uncompressed size of a single [`Compressed`] payload. A
+/// compressed payload may come from an untrusted or corrupted remote source, and both
+/// `lz4_flex` and `zstd` will happily allocate whatever uncompressed size the payload
+/// claims before validating it against the actual compressed bytes — without a cap, a
+/// few bytes of crafted input can force a multi-gigabyte allocation (a decompression
+/// bomb). 256 MiB comfortably covers a full-resolution font atlas or a large frame's
+/// draw buffers with room to spare.
+const MAX_DECOMPRESSED_SIZE: usize = 256 * 1024 * 1024;
+
+/// Codec used to compress a payload, recorded alongside the compressed bytes so the
+/// receiving side can pick the matching decompressor automatically instead of being
+/// told out-of-band which one was used.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Codec {
+    /// No compression; bytes are stored as-is.
+    None,
+    /// Fast, low-ratio compression via `lz4_flex`.
+    #[default]
+    Lz4,
+    /// Slower, higher-ratio compression via `zstd`.
+    Zstd(CompressionLevel),
+}
+
+/// Compression level for [`Codec::Zstd`], in zstd's native 1-22 range.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompressionLevel(pub i32);
+
+impl Default for CompressionLevel {
+    fn default() -> Self {
+        CompressionLevel(3)
+    }
+}
+
+impl Codec {
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+        match self {
+            Codec::None => Ok(data.to_vec()),
+            Codec::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+            Codec::Zstd(level) => zstd::bulk::compress(data, level.0).map_err(|e| CompressionError(e.to_string())),
+        }
+    }
+
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+        match self {
+            Codec::None => Ok(data.to_vec()),
+            Codec::Lz4 => {
+                // Parse the prepended size ourselves and cap it before allocating,
+                // rather than using `decompress_size_prepended` directly — it trusts
+                // this same size to size its output buffer before validating it
+                // against `data`, which is exactly the decompression-bomb risk
+                // `MAX_DECOMPRESSED_SIZE` guards against.
+                let (uncompressed_size, rest) = lz4_flex::block::uncompressed_size(data)
+                    .map_err(|e| CompressionError(e.to_string()))?;
+                if uncompressed_size > MAX_DECOMPRESSED_SIZE {
+                    return Err(CompressionError(format!(
+                        "claimed uncompressed size {uncompressed_size} exceeds the {MAX_DECOMPRESSED_SIZE} byte limit"
+                    )));
+                }
+                lz4_flex::decompress(rest, uncompressed_size).map_err(|e| CompressionError(e.to_string()))
+            }
+            Codec::Zstd(_) => zstd::bulk::decompress(data, MAX_DECOMPRESSED_SIZE).map_err(|e| CompressionError(e.to_string())),
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT_CODEC: Cell<Codec> = Cell::new(Codec::default());
+}
+
+/// Sets the [`Codec`] used by [`serialize_compressed`] and the compressed buffer
+/// encoders in this thread going forward, overriding [`Codec::default`]. Callers that
+/// want e.g. higher-ratio `Zstd` compression for recorded sessions, at the cost of
+/// slower encoding, should call this before serializing.
+pub fn set_codec(codec: Codec) {
+    CURRENT_CODEC.with(|c| c.set(codec));
+}
+
+/// Returns the [`Codec`] that encoding will currently use, see [`set_codec`].
+pub fn current_codec() -> Codec {
+    CURRENT_CODEC.with(|c| c.get())
+}
+
+/// A compressed payload tagged with the [`Codec`] used to produce it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Compressed {
+    codec: Codec,
+    bytes: Vec<u8>,
+}
+
+impl Compressed {
+    /// Compresses `data` with `codec`. Panics if the codec fails to compress
+    /// freshly-produced in-memory data, which isn't expected to happen; only
+    /// decompression of a payload from elsewhere needs to be recoverable, see
+    /// [`decode`](Compressed::decode).
+    pub fn encode(codec: Codec, data: &[u8]) -> Self {
+        let bytes = codec.compress(data).expect("in-memory compression failed");
+        Self { codec, bytes }
+    }
+
+    pub fn decode(&self) -> Result<Vec<u8>, CompressionError> {
+        self.codec.decompress(&self.bytes)
+    }
+}
+
+/// `serialize_with` for a raw byte buffer field: compresses with [`current_codec`]
+/// on binary formats, falls back to the plain byte vector on human-readable ones
+/// (e.g. JSON) so debug dumps stay legible.
+pub fn serialize_compressed<S>(buf: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        buf.serialize(serializer)
+    } else {
+        Compressed::encode(current_codec(), buf).serialize(serializer)
+    }
+}
+
+/// `deserialize_with` counterpart to [`serialize_compressed`].
+pub fn deserialize_compressed<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        Vec::<u8>::deserialize(deserializer)
+    } else {
+        Compressed::deserialize(deserializer)?.decode().map_err(serde::de::Error::custom)
+    }
+}
@@ -1,9 +1,11 @@
-use std::borrow::{Borrow, BorrowMut};
-use std::ops::{Deref, DerefMut};
+#[cfg(feature = "imgui")]
+use std::borrow::Borrow;
+#[cfg(feature = "imgui")]
+use std::ops::DerefMut;
 use serde::{Serialize, Deserialize};
 use crate::TextureId;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Context {
     pub font_atlas: FontAtlas,
 }
@@ -23,7 +25,7 @@ impl Context {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FontAtlas {
     pub texture: FontAtlasTexture,
     pub tex_id: TextureId,
@@ -50,6 +52,12 @@ impl From<&mut imgui::FontAtlas> for FontAtlas {
 pub struct FontAtlasTexture {
     pub width: u32,
     pub height: u32,
+    /// Uncompressed RGBA8 pixel data (`width * height * 4` bytes).
+    ///
+    /// Static but re-paid in full on every serialized `Context` unless the
+    /// `compression` feature is enabled, which transparently compresses it on the
+    /// wire.
+    #[cfg_attr(feature = "compression", serde(serialize_with = "crate::serialize_compressed", deserialize_with = "crate::deserialize_compressed"))]
     pub data: Vec<u8>,
 }
 
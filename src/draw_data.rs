@@ -1,5 +1,7 @@
+#[cfg(feature = "imgui")]
 use std::borrow::Borrow;
 use serde::{Serialize, Deserialize};
+use crate::{CallbackRegistry, UnregisteredCallbackError};
 
 /// All draw data to render a Dear ImGui frame.
 #[derive(Serialize, Deserialize)]
@@ -27,16 +29,18 @@ pub struct DrawData {
 }
 
 #[cfg(feature = "imgui")]
-impl From<&imgui::DrawData> for DrawData {
-    fn from(d: &imgui::DrawData) -> Self {
-        Self {
+impl std::convert::TryFrom<&imgui::DrawData> for DrawData {
+    type Error = UnregisteredCallbackError;
+
+    fn try_from(d: &imgui::DrawData) -> Result<Self, Self::Error> {
+        Ok(Self {
             total_idx_count: d.total_idx_count,
             total_vtx_count: d.total_vtx_count,
-            cmd_lists: d.draw_lists().map(|n| n.into()).collect(),
+            cmd_lists: d.draw_lists().map(DrawList::try_from).collect::<Result<Vec<_>, _>>()?,
             display_pos: d.display_pos,
             display_size: d.display_size,
             framebuffer_scale: d.framebuffer_scale,
-        }
+        })
     }
 }
 
@@ -50,18 +54,265 @@ impl DrawData {
 #[derive(Clone, Debug)]
 pub struct DrawList {
     pub commands: Vec<DrawCmd>,
+    #[cfg_attr(all(feature = "bytemuck", feature = "compression"), serde(serialize_with = "serialize_idx_buffer_compressed", deserialize_with = "deserialize_idx_buffer_compressed"))]
+    #[cfg_attr(all(feature = "bytemuck", not(feature = "compression")), serde(serialize_with = "serialize_idx_buffer", deserialize_with = "deserialize_idx_buffer"))]
+    #[cfg_attr(all(feature = "compression", not(feature = "bytemuck")), serde(serialize_with = "serialize_idx_buffer_compressed_packed", deserialize_with = "deserialize_idx_buffer_compressed_packed"))]
     pub idx_buffer: Vec<DrawIdx>,
+    #[cfg_attr(all(feature = "bytemuck", feature = "compression"), serde(serialize_with = "serialize_vtx_buffer_compressed", deserialize_with = "deserialize_vtx_buffer_compressed"))]
+    #[cfg_attr(all(feature = "bytemuck", not(feature = "compression")), serde(serialize_with = "serialize_vtx_buffer", deserialize_with = "deserialize_vtx_buffer"))]
+    #[cfg_attr(all(feature = "compression", not(feature = "bytemuck")), serde(serialize_with = "serialize_vtx_buffer_compressed_packed", deserialize_with = "deserialize_vtx_buffer_compressed_packed"))]
     pub vtx_buffer: Vec<DrawVert>,
 }
 
+// Binary formats (bincode and friends) pay for `DrawVert`/`DrawIdx` element-by-element
+// serde, which dominates frame cost for large draw lists. When both sides are `Pod`,
+// cast the whole buffer to bytes and ship it as a single blob instead. Human-readable
+// formats (JSON) keep the element-wise path so debug dumps stay legible.
+#[cfg(all(feature = "bytemuck", not(feature = "compression")))]
+fn serialize_vtx_buffer<S>(buf: &[DrawVert], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    if serializer.is_human_readable() {
+        buf.serialize(serializer)
+    } else {
+        serializer.serialize_bytes(bytemuck::cast_slice(buf))
+    }
+}
+
+#[cfg(all(feature = "bytemuck", not(feature = "compression")))]
+fn deserialize_vtx_buffer<'de, D>(deserializer: D) -> Result<Vec<DrawVert>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        Vec::<DrawVert>::deserialize(deserializer)
+    } else {
+        let bytes = <&[u8]>::deserialize(deserializer)?;
+        pod_vec_from_bytes(bytes)
+    }
+}
+
+#[cfg(all(feature = "bytemuck", not(feature = "compression")))]
+fn serialize_idx_buffer<S>(buf: &[DrawIdx], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    if serializer.is_human_readable() {
+        buf.serialize(serializer)
+    } else {
+        serializer.serialize_bytes(bytemuck::cast_slice(buf))
+    }
+}
+
+#[cfg(all(feature = "bytemuck", not(feature = "compression")))]
+fn deserialize_idx_buffer<'de, D>(deserializer: D) -> Result<Vec<DrawIdx>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        Vec::<DrawIdx>::deserialize(deserializer)
+    } else {
+        let bytes = <&[u8]>::deserialize(deserializer)?;
+        pod_vec_from_bytes(bytes)
+    }
+}
+
+/// Reconstructs a `Vec<T>` from a raw byte buffer, for the binary deserialize paths
+/// above. Unlike `bytemuck::cast_slice`, this never panics on a buffer whose length
+/// isn't a multiple of `size_of::<T>()` or whose alignment doesn't match `T` — both
+/// entirely plausible for bytes coming from an untrusted or corrupted remote source —
+/// surfacing a deserialize error instead.
+#[cfg(feature = "bytemuck")]
+fn pod_vec_from_bytes<T: bytemuck::Pod, E: serde::de::Error>(bytes: &[u8]) -> Result<Vec<T>, E> {
+    let elem_size = core::mem::size_of::<T>();
+    if elem_size == 0 || !bytes.len().is_multiple_of(elem_size) {
+        return Err(E::custom(format_args!(
+            "byte buffer of length {} is not a multiple of element size {}",
+            bytes.len(),
+            elem_size,
+        )));
+    }
+    // `pod_collect_to_vec` copies into a freshly allocated, correctly aligned `Vec<T>`
+    // rather than reinterpreting `bytes` in place, so a misaligned source buffer is
+    // fine here.
+    Ok(bytemuck::pod_collect_to_vec(bytes))
+}
+
+// Compresses the `bytemuck` raw-byte view above. When `bytemuck` isn't enabled, the
+// element-wise pack/unpack functions further down provide an equivalent path so
+// `compression` alone still compresses these buffers.
+#[cfg(all(feature = "bytemuck", feature = "compression"))]
+fn serialize_vtx_buffer_compressed<S>(buf: &[DrawVert], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    if serializer.is_human_readable() {
+        buf.serialize(serializer)
+    } else {
+        crate::Compressed::encode(crate::current_codec(), bytemuck::cast_slice(buf)).serialize(serializer)
+    }
+}
+
+#[cfg(all(feature = "bytemuck", feature = "compression"))]
+fn deserialize_vtx_buffer_compressed<'de, D>(deserializer: D) -> Result<Vec<DrawVert>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        Vec::<DrawVert>::deserialize(deserializer)
+    } else {
+        let compressed = crate::Compressed::deserialize(deserializer)?;
+        let bytes = compressed.decode().map_err(serde::de::Error::custom)?;
+        pod_vec_from_bytes(&bytes)
+    }
+}
+
+#[cfg(all(feature = "bytemuck", feature = "compression"))]
+fn serialize_idx_buffer_compressed<S>(buf: &[DrawIdx], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    if serializer.is_human_readable() {
+        buf.serialize(serializer)
+    } else {
+        crate::Compressed::encode(crate::current_codec(), bytemuck::cast_slice(buf)).serialize(serializer)
+    }
+}
+
+#[cfg(all(feature = "bytemuck", feature = "compression"))]
+fn deserialize_idx_buffer_compressed<'de, D>(deserializer: D) -> Result<Vec<DrawIdx>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        Vec::<DrawIdx>::deserialize(deserializer)
+    } else {
+        let compressed = crate::Compressed::deserialize(deserializer)?;
+        let bytes = compressed.decode().map_err(serde::de::Error::custom)?;
+        pod_vec_from_bytes(&bytes)
+    }
+}
+
+// Without `bytemuck` there's no `Pod` cast available, so compression packs each
+// element's fields into bytes by hand instead. Slower than the `bytemuck` path above,
+// but means enabling `compression` alone still compresses these buffers rather than
+// silently falling back to the uncompressed element-wise derive.
+#[cfg(all(feature = "compression", not(feature = "bytemuck")))]
+fn pack_vtx_buffer(buf: &[DrawVert]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(core::mem::size_of_val(buf));
+    for vert in buf {
+        bytes.extend_from_slice(&vert.pos[0].to_le_bytes());
+        bytes.extend_from_slice(&vert.pos[1].to_le_bytes());
+        bytes.extend_from_slice(&vert.uv[0].to_le_bytes());
+        bytes.extend_from_slice(&vert.uv[1].to_le_bytes());
+        bytes.extend_from_slice(&vert.col);
+    }
+    bytes
+}
+
+#[cfg(all(feature = "compression", not(feature = "bytemuck")))]
+fn unpack_vtx_buffer<E: serde::de::Error>(bytes: &[u8]) -> Result<Vec<DrawVert>, E> {
+    const ELEM_SIZE: usize = 20;
+    if !bytes.len().is_multiple_of(ELEM_SIZE) {
+        return Err(E::custom(format_args!(
+            "byte buffer of length {} is not a multiple of vertex size {}",
+            bytes.len(),
+            ELEM_SIZE,
+        )));
+    }
+    Ok(bytes
+        .chunks_exact(ELEM_SIZE)
+        .map(|c| DrawVert {
+            pos: [f32::from_le_bytes(c[0..4].try_into().unwrap()), f32::from_le_bytes(c[4..8].try_into().unwrap())],
+            uv: [f32::from_le_bytes(c[8..12].try_into().unwrap()), f32::from_le_bytes(c[12..16].try_into().unwrap())],
+            col: [c[16], c[17], c[18], c[19]],
+        })
+        .collect())
+}
+
+#[cfg(all(feature = "compression", not(feature = "bytemuck")))]
+fn pack_idx_buffer(buf: &[DrawIdx]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(core::mem::size_of_val(buf));
+    for idx in buf {
+        bytes.extend_from_slice(&idx.to_le_bytes());
+    }
+    bytes
+}
+
+#[cfg(all(feature = "compression", not(feature = "bytemuck")))]
+fn unpack_idx_buffer<E: serde::de::Error>(bytes: &[u8]) -> Result<Vec<DrawIdx>, E> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err(E::custom(format_args!(
+            "byte buffer of length {} is not a multiple of index size 2",
+            bytes.len(),
+        )));
+    }
+    Ok(bytes.chunks_exact(2).map(|c| DrawIdx::from_le_bytes([c[0], c[1]])).collect())
+}
+
+#[cfg(all(feature = "compression", not(feature = "bytemuck")))]
+fn serialize_vtx_buffer_compressed_packed<S>(buf: &[DrawVert], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    if serializer.is_human_readable() {
+        buf.serialize(serializer)
+    } else {
+        crate::Compressed::encode(crate::current_codec(), &pack_vtx_buffer(buf)).serialize(serializer)
+    }
+}
+
+#[cfg(all(feature = "compression", not(feature = "bytemuck")))]
+fn deserialize_vtx_buffer_compressed_packed<'de, D>(deserializer: D) -> Result<Vec<DrawVert>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        Vec::<DrawVert>::deserialize(deserializer)
+    } else {
+        let compressed = crate::Compressed::deserialize(deserializer)?;
+        let bytes = compressed.decode().map_err(serde::de::Error::custom)?;
+        unpack_vtx_buffer(&bytes)
+    }
+}
+
+#[cfg(all(feature = "compression", not(feature = "bytemuck")))]
+fn serialize_idx_buffer_compressed_packed<S>(buf: &[DrawIdx], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    if serializer.is_human_readable() {
+        buf.serialize(serializer)
+    } else {
+        crate::Compressed::encode(crate::current_codec(), &pack_idx_buffer(buf)).serialize(serializer)
+    }
+}
+
+#[cfg(all(feature = "compression", not(feature = "bytemuck")))]
+fn deserialize_idx_buffer_compressed_packed<'de, D>(deserializer: D) -> Result<Vec<DrawIdx>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        Vec::<DrawIdx>::deserialize(deserializer)
+    } else {
+        let compressed = crate::Compressed::deserialize(deserializer)?;
+        let bytes = compressed.decode().map_err(serde::de::Error::custom)?;
+        unpack_idx_buffer(&bytes)
+    }
+}
+
 #[cfg(feature = "imgui")]
-impl From<&imgui::DrawList> for DrawList {
-    fn from(d: &imgui::DrawList) -> Self {
-        Self {
-            commands: d.commands().map(|n| n.into()).collect(),
+impl std::convert::TryFrom<&imgui::DrawList> for DrawList {
+    type Error = UnregisteredCallbackError;
+
+    fn try_from(d: &imgui::DrawList) -> Result<Self, Self::Error> {
+        Ok(Self {
+            commands: d.commands().map(DrawCmd::try_from).collect::<Result<Vec<_>, _>>()?,
             idx_buffer: d.idx_buffer().to_vec(),
             vtx_buffer: d.vtx_buffer().iter().map(|n| n.into()).collect(),
-        }
+        })
     }
 }
 
@@ -106,20 +357,43 @@ pub enum DrawCmd {
         cmd_params: DrawCmdParams,
     },
     ResetRenderState,
-    // RawCallback {
-    //     callback: unsafe extern "C" fn(*const sys::ImDrawList, cmd: *const sys::ImDrawCmd),
-    //     raw_cmd: *const sys::ImDrawCmd,
-    // },
+    /// A named callback, identified by a stable id registered in a [`CallbackRegistry`]
+    /// on both sides of the serialization boundary, with an optional opaque payload.
+    ///
+    /// Replaces `imgui`'s `RawCallback`, which carries a raw function pointer and thus
+    /// cannot cross a serialization boundary.
+    Callback {
+        id: u32,
+        user_data: Vec<u8>,
+    },
 }
 
 #[cfg(feature = "imgui")]
-impl From<imgui::DrawCmd> for DrawCmd {
-    fn from(c: imgui::DrawCmd) -> Self {
+impl std::convert::TryFrom<imgui::DrawCmd> for DrawCmd {
+    type Error = UnregisteredCallbackError;
+
+    /// Converts an `imgui::DrawCmd`. `RawCallback` has no stable id to serialize, so
+    /// it is rejected with [`UnregisteredCallbackError`] rather than panicking;
+    /// applications that need custom callbacks to survive serialization should build
+    /// `DrawCmd::Callback` directly against a shared [`CallbackRegistry`] instead of
+    /// going through `imgui`'s raw callback mechanism.
+    fn try_from(c: imgui::DrawCmd) -> Result<Self, Self::Error> {
         match c {
-            imgui::DrawCmd::Elements { cmd_params, count } => Self::Elements { cmd_params: cmd_params.borrow().into(), count: count },
-            imgui::DrawCmd::ResetRenderState => Self::ResetRenderState,
-            imgui::DrawCmd::RawCallback { .. } => panic!("DrawCmd::RawCallback not supported")
+            imgui::DrawCmd::Elements { cmd_params, count } => Ok(Self::Elements { cmd_params: cmd_params.borrow().into(), count }),
+            imgui::DrawCmd::ResetRenderState => Ok(Self::ResetRenderState),
+            imgui::DrawCmd::RawCallback { .. } => Err(UnregisteredCallbackError::unresolvable()),
+        }
+    }
+}
+
+impl DrawCmd {
+    /// Replays this command against `registry`, invoking the matching callback for a
+    /// `Callback` variant. A no-op for the other variants.
+    pub fn replay_callback(&self, registry: &CallbackRegistry) -> Result<(), UnregisteredCallbackError> {
+        if let DrawCmd::Callback { id, user_data } = self {
+            registry.invoke(*id, user_data)?;
         }
+        Ok(())
     }
 }
 
@@ -152,6 +426,7 @@ pub type DrawIdx = u16;
 #[repr(C)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
 pub struct DrawVert {
     pub pos: [f32; 2],
     pub uv: [f32; 2],
@@ -192,7 +467,7 @@ impl TextureId {
 #[cfg(feature = "imgui")]
 impl From<imgui::TextureId> for TextureId {
     fn from(i: imgui::TextureId) -> Self {
-        Self(unsafe { core::mem::transmute(i) })
+        Self(unsafe { core::mem::transmute::<imgui::TextureId, usize>(i) })
     }
 }
 
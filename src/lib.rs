@@ -1,10 +1,22 @@
 mod util;
 mod draw_data;
 mod context;
+mod cache;
+mod callback;
+#[cfg(feature = "recorder")]
+mod recorder;
+#[cfg(feature = "compression")]
+mod compression;
 
 pub use util::*;
 pub use draw_data::*;
 pub use context::*;
+pub use cache::*;
+pub use callback::*;
+#[cfg(feature = "recorder")]
+pub use recorder::*;
+#[cfg(feature = "compression")]
+pub use compression::*;
 
 #[cfg(feature = "imgui")]
 pub use imgui;
@@ -0,0 +1,115 @@
+use std::io::{self, Read, Write};
+use serde::de::DeserializeOwned;
+use serde::{Serialize, Deserialize};
+use crate::{Context, DrawData};
+
+/// On-disk header written once at the start of a recording, making it self-contained:
+/// replay doesn't need the original `imgui::Context` to know the display geometry or
+/// font atlas that was active when the frames were captured.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecordingHeader {
+    /// `CARGO_PKG_VERSION` of this crate at capture time, recorded for debugging
+    /// incompatible recordings; not currently enforced on replay.
+    pub crate_version: String,
+    pub display_size: [f32; 2],
+    pub framebuffer_scale: [f32; 2],
+    pub context: Context,
+}
+
+impl RecordingHeader {
+    pub fn new(context: Context, display_size: [f32; 2], framebuffer_scale: [f32; 2]) -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            display_size,
+            framebuffer_scale,
+            context,
+        }
+    }
+}
+
+/// Records a stream of [`DrawData`] frames, preceded by a [`RecordingHeader`], to any
+/// `Write` — useful for debugging, regression tests, and golden-image comparisons
+/// against a renderer. Read back frame-by-frame with [`Player`].
+pub struct Recorder<W> {
+    writer: W,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Creates a recorder and immediately writes `header`.
+    pub fn new(mut writer: W, header: &RecordingHeader) -> io::Result<Self> {
+        write_framed(&mut writer, header)?;
+        Ok(Self { writer })
+    }
+
+    /// Appends one frame to the recording, length-prefixed so [`Player`] can read the
+    /// stream back incrementally without knowing frame boundaries ahead of time.
+    pub fn record(&mut self, frame: &DrawData) -> io::Result<()> {
+        write_framed(&mut self.writer, frame)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads back a recording written by [`Recorder`], yielding each captured frame in
+/// order.
+pub struct Player<R> {
+    reader: R,
+    pub header: RecordingHeader,
+}
+
+impl<R: Read> Player<R> {
+    /// Reads the recording's header and returns a player positioned at the first
+    /// frame.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let header = read_framed(&mut reader)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "recording is empty: missing header"))??;
+        Ok(Self { reader, header })
+    }
+}
+
+impl<R: Read> Iterator for Player<R> {
+    type Item = io::Result<DrawData>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        read_framed(&mut self.reader)
+    }
+}
+
+fn write_framed<W: Write, T: Serialize>(writer: &mut W, value: &T) -> io::Result<()> {
+    let bytes = bincode::serialize(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Upper bound on a single framed record's byte length. A recording may be truncated
+/// or corrupted (e.g. a dropped byte shifts every later length prefix), so the 8-byte
+/// prefix read off the wire can't be trusted to size an allocation directly — without
+/// a cap, a handful of corrupted bytes could claim an exabyte frame and abort the
+/// process on allocation failure instead of returning the `io::Result` this function
+/// promises. 256 MiB comfortably covers a full-resolution font atlas or a large
+/// frame's draw buffers with room to spare.
+const MAX_FRAME_LEN: u64 = 256 * 1024 * 1024;
+
+fn read_framed<R: Read, T: DeserializeOwned>(reader: &mut R) -> Option<io::Result<T>> {
+    let mut len_bytes = [0u8; 8];
+    match reader.read_exact(&mut len_bytes) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return None,
+        Err(e) => return Some(Err(e)),
+    }
+    let len = u64::from_le_bytes(len_bytes);
+    if len > MAX_FRAME_LEN {
+        return Some(Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("framed record length {len} exceeds the {MAX_FRAME_LEN} byte limit"),
+        )));
+    }
+    let mut buf = vec![0u8; len as usize];
+    if let Err(e) = reader.read_exact(&mut buf) {
+        return Some(Err(e));
+    }
+    Some(bincode::deserialize(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)))
+}
@@ -1,36 +1,94 @@
 use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
 use crate::TextureId;
 
+/// Minimal FxHash-style hasher (the `rustc-hash` crate's rotate-xor-multiply step and
+/// seed, applied one byte at a time in `write`, so its hashes differ from that crate's)
+/// used as the backing hasher for [`Textures`] so that [`Textures::new`] can be a
+/// `const fn`.
+#[derive(Default)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+const FX_SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+impl Hasher for FxHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash = (self.hash.rotate_left(5) ^ (*byte as u64)).wrapping_mul(FX_SEED);
+        }
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.hash = (self.hash.rotate_left(5) ^ (i as u64)).wrapping_mul(FX_SEED);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+type FxBuildHasher = BuildHasherDefault<FxHasher>;
+
 /// Generic texture mapping for use by renderers.
-#[derive(Debug, Default)]
+///
+/// Freed ids are recycled by [`insert`](Textures::insert) instead of growing `next`
+/// forever, so renderers that churn textures don't leak id space.
+///
+/// Recycled ids are not generation-tagged: `TextureId`'s bit layout must stay
+/// transmute-compatible with `imgui::TextureId`, so there's nowhere to pack a
+/// generation counter without changing that representation.
+#[derive(Debug)]
 pub struct Textures<T> {
-    textures: HashMap<usize, T>,
+    textures: HashMap<usize, T, FxBuildHasher>,
+    free: Vec<usize>,
     next: usize,
 }
 
+impl<T> Default for Textures<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> Textures<T> {
-    // TODO: hasher like rustc_hash::FxHashMap or something would let this be
-    // `const fn`
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Textures {
-            textures: HashMap::new(),
+            textures: HashMap::with_hasher(FxBuildHasher::new()),
+            free: Vec::new(),
             next: 0,
         }
     }
 
     pub fn insert(&mut self, texture: T) -> TextureId {
-        let id = self.next;
+        let id = self.free.pop().unwrap_or_else(|| {
+            let id = self.next;
+            self.next += 1;
+            id
+        });
         self.textures.insert(id, texture);
-        self.next += 1;
         TextureId::from(id)
     }
 
+    /// Inserts `texture` under `id` directly, bypassing the free list. If `id` had
+    /// previously been [`remove`](Textures::remove)d, this also takes it back out of
+    /// the free list so a later [`insert`](Textures::insert) can't pop the same id and
+    /// collide with the entry just written here.
     pub fn replace(&mut self, id: TextureId, texture: T) -> Option<T> {
+        self.free.retain(|&free_id| free_id != id.0);
         self.textures.insert(id.0, texture)
     }
 
     pub fn remove(&mut self, id: TextureId) -> Option<T> {
-        self.textures.remove(&id.0)
+        let removed = self.textures.remove(&id.0);
+        if removed.is_some() {
+            self.free.push(id.0);
+        }
+        removed
     }
 
     pub fn get(&self, id: TextureId) -> Option<&T> {
@@ -40,4 +98,50 @@ impl<T> Textures<T> {
     pub fn get_mut(&mut self, id: TextureId) -> Option<&mut T> {
         self.textures.get_mut(&id.0)
     }
+
+    /// Number of textures currently stored.
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    /// Returns `true` if no textures are currently stored.
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+
+    /// Iterates over all live textures and their ids.
+    pub fn iter(&self) -> impl Iterator<Item = (TextureId, &T)> {
+        self.textures.iter().map(|(&id, texture)| (TextureId::from(id), texture))
+    }
+
+    /// Removes all textures, empties the free list and restarts id allocation at 0.
+    pub fn clear(&mut self) {
+        self.textures.clear();
+        self.free.clear();
+        self.next = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_at_a_freed_id_keeps_it_out_of_the_free_list() {
+        let mut textures = Textures::new();
+        let a = textures.insert("a");
+        let b = textures.insert("b");
+
+        textures.remove(a);
+        textures.replace(a, "a-replacement");
+
+        // `a` must not still be on the free list, or this `insert` could pop it and
+        // collide with the entry `replace` just wrote.
+        let c = textures.insert("c");
+        assert_ne!(c, a);
+        assert_ne!(c, b);
+
+        assert_eq!(textures.get(a), Some(&"a-replacement"));
+        assert_eq!(textures.get(c), Some(&"c"));
+    }
 }